@@ -1,7 +1,41 @@
-use std::{error, fmt, sync::Arc};
+use std::{
+    error, fmt,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use druid::Data;
 
+/// Machine-readable `reason` code carried by Spotify player/Web API error
+/// bodies, alongside the human-facing message. Unknown codes are preserved
+/// verbatim in [`SpotifyReason::Other`].
+#[derive(Clone, Debug, Data, PartialEq, Eq)]
+pub enum SpotifyReason {
+    PremiumRequired,
+    NoPrevTrack,
+    NoNextTrack,
+    Unknown,
+    DeviceNotFound,
+    ContextDisallow,
+    Other(Arc<str>),
+}
+
+impl SpotifyReason {
+    /// Parse a raw `reason` string into a typed code, keeping unrecognized
+    /// values in `Other`.
+    fn from_code(code: &str) -> Self {
+        match code {
+            "PREMIUM_REQUIRED" => Self::PremiumRequired,
+            "NO_PREV_TRACK" => Self::NoPrevTrack,
+            "NO_NEXT_TRACK" => Self::NoNextTrack,
+            "UNKNOWN" => Self::Unknown,
+            "DEVICE_NOT_FOUND" => Self::DeviceNotFound,
+            "CONTEXT_DISALLOW" => Self::ContextDisallow,
+            other => Self::Other(other.into()),
+        }
+    }
+}
+
 /// Detailed HTTP error information for better error reporting.
 #[derive(Clone, Debug, Data)]
 pub struct HttpErrorDetails {
@@ -17,6 +51,12 @@ pub struct HttpErrorDetails {
     pub body: Option<Arc<str>>,
     /// A user-friendly error message derived from the response
     pub message: Arc<str>,
+    /// How long the server asked us to wait before retrying, parsed from the
+    /// `Retry-After` header (present on `429 Too Many Requests` responses).
+    #[data(eq)]
+    pub retry_after: Option<Duration>,
+    /// Machine-readable `reason` code from the response body, when present.
+    pub reason: Option<SpotifyReason>,
 }
 
 impl HttpErrorDetails {
@@ -26,13 +66,16 @@ impl HttpErrorDetails {
         url: impl Into<Arc<str>>,
         method: impl Into<Arc<str>>,
         body: Option<impl Into<Arc<str>>>,
+        headers: &[(Arc<str>, Arc<str>)],
     ) -> Self {
         let status_text: Arc<str> = status_text.into();
         let body: Option<Arc<str>> = body.map(Into::into);
-        
+
         // Try to extract a meaningful message from the response body
         let message = Self::extract_message(&body, &status_text, status_code);
-        
+        let reason = Self::extract_reason(&body);
+        let retry_after = Self::retry_after_header(headers).and_then(Self::parse_retry_after);
+
         Self {
             status_code,
             status_text,
@@ -40,8 +83,43 @@ impl HttpErrorDetails {
             method: method.into(),
             body,
             message,
+            retry_after,
+            reason,
         }
     }
+
+    /// Read the machine-readable `error.reason` code from the response body,
+    /// if the body is JSON and carries one.
+    fn extract_reason(body: &Option<Arc<str>>) -> Option<SpotifyReason> {
+        let body = body.as_ref()?;
+        let json = serde_json::from_str::<serde_json::Value>(body).ok()?;
+        let reason = json.get("error")?.get("reason")?.as_str()?;
+        Some(SpotifyReason::from_code(reason))
+    }
+
+    /// Find the `Retry-After` header value, matching the name case-insensitively.
+    fn retry_after_header(headers: &[(Arc<str>, Arc<str>)]) -> Option<&str> {
+        headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("retry-after"))
+            .map(|(_, value)| value.as_ref())
+    }
+
+    /// Parse a `Retry-After` header value into a wait duration. The header is
+    /// either an integer number of seconds or an HTTP-date; for the latter we
+    /// compute the remaining time from now, clamping values in the past to zero.
+    fn parse_retry_after(value: &str) -> Option<Duration> {
+        let value = value.trim();
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        let deadline = httpdate::parse_http_date(value).ok()?;
+        Some(
+            deadline
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+        )
+    }
     
     /// Try to extract a user-friendly error message from the response body.
     /// Spotify API typically returns JSON with "error" or "error.message" fields.
@@ -106,6 +184,31 @@ pub enum Error {
     WebApiError(Arc<str>),
     /// A detailed HTTP error with status code, response body, etc.
     HttpError(HttpErrorDetails),
+    /// The server throttled us (HTTP 429). `retry_after` is taken from the
+    /// `Retry-After` header when present so callers can back off accordingly.
+    RateLimited {
+        details: HttpErrorDetails,
+        #[data(eq)]
+        retry_after: Option<Duration>,
+    },
+    /// Authentication failed or the session expired (HTTP 401).
+    Unauthorized(HttpErrorDetails),
+    /// The request was understood but refused (HTTP 403).
+    Forbidden(HttpErrorDetails),
+    /// The requested resource does not exist (HTTP 404).
+    NotFound(HttpErrorDetails),
+    /// The server failed to handle the request (HTTP 5xx).
+    ServerError(HttpErrorDetails),
+    /// The request was malformed or otherwise rejected (other 4xx).
+    BadRequest(HttpErrorDetails),
+    /// A transport-level failure from the HTTP client (connection, TLS, I/O).
+    Transport(Arc<ureq::Error>),
+    /// A response body failed to (de)serialize. `context` describes what we
+    /// were decoding; `source` is the underlying parser error.
+    Json {
+        context: Arc<str>,
+        source: Arc<serde_json::Error>,
+    },
 }
 
 impl Error {
@@ -121,18 +224,179 @@ impl Error {
         url: impl Into<Arc<str>>,
         method: impl Into<Arc<str>>,
         body: Option<impl Into<Arc<str>>>,
+        headers: &[(Arc<str>, Arc<str>)],
     ) -> Self {
-        Error::HttpError(HttpErrorDetails::new(status_code, status_text, url, method, body))
+        Error::HttpError(HttpErrorDetails::new(
+            status_code,
+            status_text,
+            url,
+            method,
+            body,
+            headers,
+        ))
+    }
+
+    /// Build an `Error` from an HTTP response, routing the status code to the
+    /// most specific variant: 429→`RateLimited`, 401→`Unauthorized`,
+    /// 403→`Forbidden`, 404→`NotFound`, 5xx→`ServerError`, other 4xx→
+    /// `BadRequest`, and anything else to the generic `HttpError`.
+    pub fn from_response(
+        status_code: u16,
+        status_text: impl Into<Arc<str>>,
+        url: impl Into<Arc<str>>,
+        method: impl Into<Arc<str>>,
+        body: Option<impl Into<Arc<str>>>,
+        headers: &[(Arc<str>, Arc<str>)],
+    ) -> Self {
+        let details = HttpErrorDetails::new(status_code, status_text, url, method, body, headers);
+        match status_code {
+            429 => Error::RateLimited {
+                retry_after: details.retry_after,
+                details,
+            },
+            401 => Error::Unauthorized(details),
+            403 => Error::Forbidden(details),
+            404 => Error::NotFound(details),
+            500..=599 => Error::ServerError(details),
+            400..=499 => Error::BadRequest(details),
+            _ => Error::HttpError(details),
+        }
+    }
+
+    /// The machine-readable Spotify `reason` code, when the error carries one.
+    /// Lets playback code react to specific conditions — e.g. suppress a
+    /// spurious toast on `NO_NEXT_TRACK` or show an upsell on `PREMIUM_REQUIRED`.
+    pub fn reason(&self) -> Option<&SpotifyReason> {
+        match self {
+            Self::HttpError(details)
+            | Self::Unauthorized(details)
+            | Self::Forbidden(details)
+            | Self::NotFound(details)
+            | Self::ServerError(details)
+            | Self::BadRequest(details)
+            | Self::RateLimited { details, .. } => details.reason.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Human-readable one-line summary suitable for display in the UI.
+    pub fn summary(&self) -> String {
+        match self {
+            Self::WebApiError(err) => err.to_string(),
+            Self::HttpError(details)
+            | Self::Unauthorized(details)
+            | Self::Forbidden(details)
+            | Self::NotFound(details)
+            | Self::ServerError(details)
+            | Self::BadRequest(details) => details.summary(),
+            Self::RateLimited { retry_after, .. } => match retry_after {
+                Some(wait) => format!("Rate limited — retry in {}s", wait.as_secs()),
+                None => "Rate limited".to_string(),
+            },
+            Self::Transport(err) => err.to_string(),
+            Self::Json { context, source } => format!("{}: {}", context, source),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err.as_ref()),
+            Self::Json { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
     }
 }
 
-impl error::Error for Error {}
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Self {
+        Error::Transport(Arc::new(err))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json {
+            context: "failed to parse JSON response".into(),
+            source: Arc::new(err),
+        }
+    }
+}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Self::WebApiError(err) => f.write_str(err),
-            Self::HttpError(details) => write!(f, "{}", details.summary()),
+        f.write_str(&self.summary())
+    }
+}
+
+/// Request retry logic keyed off the error classification in [`Error`].
+pub mod retry {
+    use std::{thread, time::Duration};
+
+    use super::Error;
+
+    /// Base delay for the exponential backoff.
+    const BASE_DELAY: Duration = Duration::from_millis(250);
+    /// Upper bound on a single backoff delay (before jitter).
+    const MAX_DELAY: Duration = Duration::from_secs(8);
+
+    impl Error {
+        /// Whether a request that failed with this error is worth retrying.
+        /// We retry transient server-side conditions (5xx, throttling) but not
+        /// client errors, which would fail identically on every attempt.
+        fn is_retryable(&self) -> bool {
+            matches!(self, Error::ServerError(_) | Error::RateLimited { .. })
+        }
+    }
+
+    /// Run `request` until it succeeds or `max_attempts` is reached, retrying
+    /// only on errors the classification marks as transient.
+    ///
+    /// `RateLimited` errors sleep for exactly the server-provided `Retry-After`
+    /// duration when available; everything else uses capped exponential backoff
+    /// with jitter. The last error is returned once attempts are exhausted.
+    pub fn execute<T, F>(max_attempts: u32, mut request: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Result<T, Error>,
+    {
+        let max_attempts = max_attempts.max(1);
+        let mut attempt = 0;
+        loop {
+            match request() {
+                Ok(value) => return Ok(value),
+                Err(err) => {
+                    attempt += 1;
+                    if attempt >= max_attempts || !err.is_retryable() {
+                        return Err(err);
+                    }
+                    thread::sleep(delay_for(&err, attempt - 1));
+                }
+            }
+        }
+    }
+
+    /// Pick the sleep duration before the next attempt. `attempt` is the
+    /// zero-based index of the attempt that just failed.
+    fn delay_for(err: &Error, attempt: u32) -> Duration {
+        if let Error::RateLimited {
+            retry_after: Some(wait),
+            ..
+        } = err
+        {
+            return *wait;
         }
+        backoff(attempt)
+    }
+
+    /// Capped exponential backoff with full jitter of up to the current delay.
+    fn backoff(attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        let delay = BASE_DELAY
+            .checked_mul(factor)
+            .unwrap_or(MAX_DELAY)
+            .min(MAX_DELAY);
+        let jitter = delay.mul_f64(rand::random::<f64>());
+        delay + jitter
     }
 }